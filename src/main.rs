@@ -1,14 +1,40 @@
-use crate::map::Map;
+use std::env;
+use std::fs;
+
+use crate::map::{Map, Tool};
 
 mod map;
 
+const SAMPLE_INPUT: &str = "depth: 6084\ntarget: 14,709\n";
+const RENDER_FLAG: &str = "--render";
+
 fn main() {
-    // let mut map = Map::new(510, (10, 10));
-    let mut map = Map::new(6084, (14, 709));
-    let risk_level = map.get_risk_level((0, 0), (14, 709));
+    let args: Vec<String> = env::args().skip(1).collect();
+    let render = args.iter().any(|arg| arg == RENDER_FLAG);
+    let path = args.iter().find(|arg| *arg != RENDER_FLAG);
+
+    let input = match path {
+        Some(path) => fs::read_to_string(path).unwrap_or_else(|err| panic!("failed to read {}: {}", path, err)),
+        None => SAMPLE_INPUT.to_string(),
+    };
 
+    let mut map = Map::from_input(&input).expect("failed to parse input");
+    let target = map.target();
+
+    let risk_level = map.get_risk_level((0, 0), target);
     println!("Risk level: {}", risk_level);
 
     let distance = map.find_distance_to_target();
     println!("Distance to target: {}", distance);
+
+    let distance_astar = map.find_distance_to_target_astar();
+    println!("Distance to target (A*): {}", distance_astar);
+
+    if render {
+        let goal = (target.0, target.1, Tool::Torch);
+        let path = map
+            .shortest_path((0, 0, Tool::Torch), goal)
+            .expect("target is always reachable");
+        println!("{}", map.render_with_path((0, 0), target, &path));
+    }
 }
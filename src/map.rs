@@ -1,10 +1,33 @@
-use std::collections::{HashMap};
-use petgraph::algo::dijkstra;
-use petgraph::prelude::*;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt;
 
 const MOVE_COST: usize = 1;
 const CHANGE_GEAR_COST: usize = 7;
 
+type SearchResult = (usize, Option<Vec<(usize, usize, Tool)>>);
+
+#[derive(Debug)]
+pub enum ParseError {
+    MissingDepth,
+    MissingTarget,
+    InvalidDepth,
+    InvalidTarget,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::MissingDepth => write!(f, "missing \"depth: <n>\" line"),
+            ParseError::MissingTarget => write!(f, "missing \"target: <x>,<y>\" line"),
+            ParseError::InvalidDepth => write!(f, "depth line did not contain a valid number"),
+            ParseError::InvalidTarget => write!(f, "target line did not contain a valid \"x,y\" pair"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
 #[derive(Copy, Clone)]
 pub enum RegionType {
     Rocky,
@@ -22,7 +45,7 @@ impl RegionType {
     }
 }
 
-#[derive(Copy, Clone, Hash, Eq, PartialEq)]
+#[derive(Copy, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
 pub enum Tool {
     Torch,
     ClimbingGear,
@@ -58,6 +81,40 @@ impl Map {
         }
     }
 
+    pub fn from_input(input: &str) -> Result<Self, ParseError> {
+        let mut lines = input.lines();
+
+        let depth = lines
+            .next()
+            .ok_or(ParseError::MissingDepth)?
+            .trim()
+            .rsplit(':')
+            .next()
+            .ok_or(ParseError::MissingDepth)?
+            .trim()
+            .parse()
+            .map_err(|_| ParseError::InvalidDepth)?;
+
+        let target_line = lines.next().ok_or(ParseError::MissingTarget)?;
+        let target_coords = target_line
+            .trim()
+            .rsplit(':')
+            .next()
+            .ok_or(ParseError::MissingTarget)?
+            .trim();
+        let (target_x, target_y) = target_coords
+            .split_once(',')
+            .ok_or(ParseError::InvalidTarget)?;
+        let target_x = target_x.trim().parse().map_err(|_| ParseError::InvalidTarget)?;
+        let target_y = target_y.trim().parse().map_err(|_| ParseError::InvalidTarget)?;
+
+        Ok(Map::new(depth, (target_x, target_y)))
+    }
+
+    pub fn target(&self) -> (usize, usize) {
+        self.target
+    }
+
     pub fn get_risk_level(&mut self, from: (usize, usize), to: (usize, usize)) -> usize {
         let mut risk_level = 0;
         for x in from.0..=to.0 {
@@ -74,54 +131,171 @@ impl Map {
     }
 
     pub fn find_distance_to_target(&mut self) -> usize {
-        let graph = self.build_graph();
+        let start = (0, 0, Tool::Torch);
+        let goal = (self.target.0, self.target.1, Tool::Torch);
+        self.shortest_time(start, goal).expect("target is always reachable")
+    }
 
-        let zero_index = graph.node_indices().find(|n| graph[*n] == (0, 0, Tool::Torch)).unwrap();
-        let target_index = graph.node_indices().find(|n| graph[*n] == (self.target.0, self.target.1, Tool::Torch)).unwrap();
-        let distances = dijkstra(&graph, zero_index, Some(target_index), |e| *e.weight());
-        distances[&target_index]
+    /// Dijkstra search over `(x, y, tool)` states, generalized to arbitrary
+    /// start and goal states so callers can plan partial or multi-leg routes.
+    pub fn shortest_time(&mut self, start: (usize, usize, Tool), goal: (usize, usize, Tool)) -> Option<usize> {
+        self.search(start, goal, |_, _| 0, false).map(|(cost, _)| cost)
     }
 
-    fn build_graph(&mut self) -> UnGraph<(usize, usize, Tool), usize> {
-        let mut graph = UnGraph::new_undirected();
-        for x in 0..=(self.target.0 * 3) {
-            for y in 0..=(self.target.1 * 3) {
-                let region_type = self.get_region_type((x, y));
-                let tools = region_type.get_tools();
-                for tool in tools.iter() {
-                    graph.add_node((x, y, *tool));
+    /// Same search as `shortest_time`, but keeps predecessor links so the
+    /// actual route can be reconstructed instead of just its cost.
+    pub fn shortest_path(
+        &mut self,
+        start: (usize, usize, Tool),
+        goal: (usize, usize, Tool),
+    ) -> Option<Vec<(usize, usize, Tool)>> {
+        self.search(start, goal, |_, _| 0, true).and_then(|(_, path)| path)
+    }
+
+    /// Shared lazy frontier search behind `shortest_time`, `shortest_path` and
+    /// `find_distance_to_target_astar`: a plain Dijkstra when `heuristic`
+    /// always returns 0, A* otherwise, with predecessor tracking gated by
+    /// `track_path` so callers that only need the cost skip that bookkeeping.
+    fn search(
+        &mut self,
+        start: (usize, usize, Tool),
+        goal: (usize, usize, Tool),
+        heuristic: impl Fn(&Self, (usize, usize, Tool)) -> usize,
+        track_path: bool,
+    ) -> Option<SearchResult> {
+        self.distances.clear();
+        self.distances.insert(start, 0);
+        let mut predecessors = HashMap::new();
+
+        let mut frontier = BinaryHeap::new();
+        frontier.push(Reverse((heuristic(self, start), 0usize, start.0, start.1, start.2)));
+
+        while let Some(Reverse((_, cost, x, y, tool))) = frontier.pop() {
+            if (x, y, tool) == goal {
+                let path = track_path.then(|| Self::reconstruct_path(&predecessors, start, goal));
+                return Some((cost, path));
+            }
+
+            if cost > *self.distances.get(&(x, y, tool)).unwrap_or(&usize::MAX) {
+                continue;
+            }
+
+            for (next_x, next_y, next_tool, step_cost) in self.get_successors((x, y, tool)) {
+                let next_cost = cost + step_cost;
+                let best = self.distances.entry((next_x, next_y, next_tool)).or_insert(usize::MAX);
+                if next_cost < *best {
+                    *best = next_cost;
+                    if track_path {
+                        predecessors.insert((next_x, next_y, next_tool), (x, y, tool));
+                    }
+                    let priority = next_cost + heuristic(self, (next_x, next_y, next_tool));
+                    frontier.push(Reverse((priority, next_cost, next_x, next_y, next_tool)));
                 }
             }
         }
-        for node in graph.node_indices() {
-            let coords = graph[node];
-            let current_region_type = self.get_region_type((coords.0, coords.1));
-            let current_tools = current_region_type.get_tools();
-            let neighbours = self.get_neighbours((coords.0, coords.1));
-            for neighbour in neighbours {
-                let region_type = self.get_region_type(neighbour);
-                let tools = region_type.get_tools();
-                for tool in tools.iter() {
-                    let neighbour = graph.node_indices().find(|n| graph[*n] == (neighbour.0, neighbour.1, *tool));
-                    if neighbour.is_none() {
-                        continue;
-                    }
 
-                    let neighbour = neighbour.unwrap();
-                    if tool == &coords.2 {
-                        graph.add_edge(node, neighbour, MOVE_COST);
-                    } else if current_tools.contains(tool) {
-                        graph.add_edge(node, neighbour, MOVE_COST + CHANGE_GEAR_COST);
+        None
+    }
+
+    fn reconstruct_path(
+        predecessors: &HashMap<(usize, usize, Tool), (usize, usize, Tool)>,
+        start: (usize, usize, Tool),
+        goal: (usize, usize, Tool),
+    ) -> Vec<(usize, usize, Tool)> {
+        let mut path = vec![goal];
+        let mut current = goal;
+        while current != start {
+            current = predecessors[&current];
+            path.push(current);
+        }
+        path.reverse();
+        path
+    }
+
+    /// Renders the region grid from `from` to `to` using the canonical
+    /// glyphs (`.` rocky, `=` wet, `|` narrow), with `M` at the origin and
+    /// `T` at the target.
+    pub fn render(&mut self, from: (usize, usize), to: (usize, usize)) -> String {
+        self.render_with_path(from, to, &[])
+    }
+
+    /// Like `render`, but overlays `path` on the grid, marking each visited
+    /// cell with the tool held there (`C` climbing gear, `T` torch, `.` none).
+    pub fn render_with_path(&mut self, from: (usize, usize), to: (usize, usize), path: &[(usize, usize, Tool)]) -> String {
+        let overlay: HashMap<(usize, usize), Tool> =
+            path.iter().map(|&(x, y, tool)| ((x, y), tool)).collect();
+
+        let mut output = String::new();
+        for y in from.1..=to.1 {
+            for x in from.0..=to.0 {
+                let glyph = if let Some(tool) = overlay.get(&(x, y)) {
+                    match tool {
+                        Tool::Torch => 'T',
+                        Tool::ClimbingGear => 'C',
+                        Tool::None => '.',
                     }
-                }
+                } else if (x, y) == (0, 0) {
+                    'M'
+                } else if (x, y) == self.target {
+                    'T'
+                } else {
+                    match self.get_region_type((x, y)) {
+                        RegionType::Rocky => '.',
+                        RegionType::Wet => '=',
+                        RegionType::Narrow => '|',
+                    }
+                };
+                output.push(glyph);
+            }
+            output.push('\n');
+        }
+        output
+    }
+
+    pub fn find_distance_to_target_astar(&mut self) -> usize {
+        let start = (0, 0, Tool::Torch);
+        let goal = (self.target.0, self.target.1, Tool::Torch);
+        self.search(start, goal, Self::heuristic, false)
+            .map(|(cost, _)| cost)
+            .expect("target is always reachable")
+    }
+
+    fn heuristic(&self, state: (usize, usize, Tool)) -> usize {
+        let (x, y, tool) = state;
+        let manhattan = (self.target.0 as isize - x as isize).unsigned_abs()
+            + (self.target.1 as isize - y as isize).unsigned_abs();
+        manhattan + if tool == Tool::Torch { 0 } else { CHANGE_GEAR_COST }
+    }
+
+    fn get_successors(&mut self, state: (usize, usize, Tool)) -> Vec<(usize, usize, Tool, usize)> {
+        let (x, y, tool) = state;
+        let mut successors = Vec::new();
+
+        for (next_x, next_y) in self.get_neighbours((x, y)) {
+            let region_type = self.get_region_type((next_x, next_y));
+            if region_type.get_tools().contains(&tool) {
+                successors.push((next_x, next_y, tool, MOVE_COST));
+            }
+        }
+
+        let current_region_type = self.get_region_type((x, y));
+        for &other_tool in current_region_type.get_tools().iter() {
+            if other_tool != tool {
+                successors.push((x, y, other_tool, CHANGE_GEAR_COST));
             }
         }
 
-        graph
+        successors
     }
 
     fn get_neighbours(&mut self, coords: (usize, usize)) -> Vec<(usize, usize)> {
         let mut neighbours = Vec::new();
+        if coords.0 > 0 {
+            neighbours.push((coords.0 - 1, coords.1));
+        }
+        if coords.1 > 0 {
+            neighbours.push((coords.0, coords.1 - 1));
+        }
         neighbours.push((coords.0 + 1, coords.1));
         neighbours.push((coords.0, coords.1 + 1));
         neighbours
@@ -169,4 +343,103 @@ impl Map {
             (x, y) => self.get_erosion_level((x - 1, y)) * self.get_erosion_level((x, y - 1)),
         }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The canonical AoC 2018 day 22 example: depth 510, target (10, 10), answer 45.
+    #[test]
+    fn astar_matches_dijkstra_on_known_answer() {
+        let mut map = Map::new(510, (10, 10));
+        assert_eq!(map.find_distance_to_target(), 45);
+        assert_eq!(map.find_distance_to_target_astar(), 45);
+    }
+
+    #[test]
+    fn shortest_time_supports_a_non_default_goal_tool() {
+        let mut map = Map::new(510, (10, 10));
+        // (0, 0) is Rocky, so both Torch and ClimbingGear are legal there:
+        // reaching the same region with ClimbingGear is a single 7-cost swap.
+        let cost = map
+            .shortest_time((0, 0, Tool::Torch), (0, 0, Tool::ClimbingGear))
+            .unwrap();
+        assert_eq!(cost, CHANGE_GEAR_COST);
+    }
+
+    #[test]
+    fn shortest_time_supports_a_non_default_start_tool() {
+        let mut map = Map::new(510, (10, 10));
+        let goal = (10, 10, Tool::Torch);
+        let from_torch = map.shortest_time((0, 0, Tool::Torch), goal).unwrap();
+        // Starting with ClimbingGear instead of Torch can only cost as much as
+        // swapping to Torch immediately and then taking the Torch-start route.
+        let from_climbing_gear = map.shortest_time((0, 0, Tool::ClimbingGear), goal).unwrap();
+        assert!(from_climbing_gear <= from_torch + CHANGE_GEAR_COST);
+    }
+
+    #[test]
+    fn render_draws_the_canonical_glyphs_with_origin_and_target_marked() {
+        let mut map = Map::new(510, (10, 10));
+        let rendered = map.render((0, 0), (10, 10));
+        let rows: Vec<&str> = rendered.lines().collect();
+
+        assert_eq!(rows.len(), 11);
+        assert!(rows.iter().all(|row| row.len() == 11));
+        assert_eq!(rows[0].chars().next(), Some('M'));
+        assert_eq!(rows[10].chars().nth(10), Some('T'));
+        assert!(rendered.chars().all(|c| "M T.=|\n".contains(c)));
+    }
+
+    #[test]
+    fn render_with_path_marks_visited_cells_with_the_held_tool() {
+        let mut map = Map::new(510, (10, 10));
+        let path = map
+            .shortest_path((0, 0, Tool::Torch), (10, 10, Tool::Torch))
+            .expect("target is always reachable");
+
+        let rendered = map.render_with_path((0, 0), (10, 10), &path);
+        assert!(rendered.chars().any(|c| c == 'C' || c == 'T'));
+    }
+
+    #[test]
+    fn from_input_parses_a_well_formed_input() {
+        let map = Map::from_input("depth: 510\ntarget: 10,10\n").unwrap();
+        assert_eq!(map.depth, 510);
+        assert_eq!(map.target, (10, 10));
+    }
+
+    #[test]
+    fn from_input_tolerates_surrounding_whitespace_and_non_numeric_prefixes() {
+        let map = Map::from_input("  puzzle depth:   510  \n  the target:  10 , 10  \n").unwrap();
+        assert_eq!(map.depth, 510);
+        assert_eq!(map.target, (10, 10));
+    }
+
+    #[test]
+    fn from_input_rejects_an_empty_input() {
+        assert!(matches!(Map::from_input(""), Err(ParseError::MissingDepth)));
+    }
+
+    #[test]
+    fn from_input_rejects_a_missing_target_line() {
+        assert!(matches!(Map::from_input("depth: 510\n"), Err(ParseError::MissingTarget)));
+    }
+
+    #[test]
+    fn from_input_rejects_a_non_numeric_depth() {
+        assert!(matches!(
+            Map::from_input("depth: abc\ntarget: 10,10\n"),
+            Err(ParseError::InvalidDepth)
+        ));
+    }
+
+    #[test]
+    fn from_input_rejects_a_malformed_target() {
+        assert!(matches!(
+            Map::from_input("depth: 510\ntarget: 10\n"),
+            Err(ParseError::InvalidTarget)
+        ));
+    }
 }
\ No newline at end of file